@@ -0,0 +1,316 @@
+//! Generates `src/instrs.rs` (the `Opcode` enum, its `From<u16>` decoder, and
+//! its `encode` inverse) from the declarative instruction table in
+//! `instructions.in`. Keeping decode and encode derived from one source
+//! instead of two hand-written, easily-divergent bit-twiddling paths.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+struct Field {
+    name: String,
+    width: u32,
+    shift: u32,
+    is_reg: bool,
+    is_flag: bool,
+    sign_extend: bool,
+}
+
+impl Field {
+    fn parse(spec: &str) -> Self {
+        let mut parts = spec.split(':');
+        let name = parts.next().unwrap().to_string();
+        let width_shift = parts.next().unwrap();
+        let (width, shift) = width_shift.split_once('@').unwrap();
+        let mut field = Field {
+            name,
+            width: width.parse().unwrap(),
+            shift: shift.parse().unwrap(),
+            is_reg: false,
+            is_flag: false,
+            sign_extend: false,
+        };
+        for modifier in parts {
+            match modifier {
+                "reg" => field.is_reg = true,
+                "flag" => field.is_flag = true,
+                "sext" => field.sign_extend = true,
+                other => panic!("unknown field modifier `{other}` in `{spec}`"),
+            }
+        }
+        field
+    }
+
+    fn mask(&self) -> u16 {
+        (1u16 << self.width) - 1
+    }
+
+    fn ty(&self) -> &'static str {
+        if self.is_reg {
+            "RegisterIndex"
+        } else if self.is_flag {
+            "bool"
+        } else {
+            "u16"
+        }
+    }
+
+    fn decode_expr(&self) -> String {
+        let mask = self.mask();
+        let shifted = if self.shift == 0 {
+            format!("value & {mask:#x}")
+        } else {
+            format!("(value >> {}) & {mask:#x}", self.shift)
+        };
+        if self.is_reg {
+            format!("RegisterIndex(({shifted}) as u8)")
+        } else if self.is_flag {
+            format!("({shifted}) != 0")
+        } else if self.sign_extend {
+            format!("sign_extend({shifted}, {})", self.width)
+        } else {
+            shifted
+        }
+    }
+
+    /// Expression re-packing this field's bits, given it is bound by name
+    /// via a destructuring match arm (not accessed through a receiver).
+    fn encode_expr(&self) -> String {
+        let mask = self.mask();
+        let name = &self.name;
+        let shift = self.shift;
+        if self.is_reg {
+            let bits = format!("(({name}.0 as u16) & {mask:#x})");
+            if shift == 0 {
+                bits
+            } else {
+                format!("{bits} << {shift}")
+            }
+        } else if self.is_flag {
+            let bit = format!("if *{name} {{ 1 }} else {{ 0 }}");
+            if shift == 0 {
+                bit
+            } else {
+                format!("({bit}) << {shift}")
+            }
+        } else if shift == 0 {
+            format!("({name} & {mask:#x})")
+        } else {
+            format!("({name} & {mask:#x}) << {shift}")
+        }
+    }
+}
+
+struct Variant {
+    opcode: u16,
+    mode: Option<(u32, u16)>,
+    name: String,
+    fields: Vec<Field>,
+}
+
+fn parse_mode(spec: &str) -> Option<(u32, u16)> {
+    if spec == "-" {
+        return None;
+    }
+    let (bit, value) = spec.strip_prefix("bit")?.split_once('=')?;
+    Some((bit.parse().unwrap(), value.parse().unwrap()))
+}
+
+fn parse_table(source: &str) -> Vec<Variant> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut tokens = line.split_whitespace();
+            let opcode = u16::from_str_radix(tokens.next().unwrap(), 2).unwrap();
+            let mode = parse_mode(tokens.next().unwrap());
+            let name = tokens.next().unwrap().to_string();
+            let fields = tokens.map(Field::parse).collect();
+            Variant {
+                opcode,
+                mode,
+                name,
+                fields,
+            }
+        })
+        .collect()
+}
+
+fn render_enum(variants: &[Variant]) -> String {
+    let mut out = String::new();
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]\n");
+    out.push_str("pub(crate) enum Opcode {\n");
+    for variant in variants {
+        if variant.fields.is_empty() {
+            let _ = writeln!(out, "    {},", variant.name);
+            continue;
+        }
+        let _ = writeln!(out, "    {} {{", variant.name);
+        for field in &variant.fields {
+            let _ = writeln!(out, "        {}: {},", field.name, field.ty());
+        }
+        out.push_str("    },\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_decode(variants: &[Variant]) -> String {
+    let mut out = String::new();
+    out.push_str("impl From<u16> for Opcode {\n");
+    out.push_str("    fn from(value: u16) -> Self {\n");
+    out.push_str("        let op = value >> 12;\n");
+    out.push_str("        match op {\n");
+
+    let mut opcodes: Vec<u16> = variants.iter().map(|v| v.opcode).collect();
+    opcodes.sort_unstable();
+    opcodes.dedup();
+
+    for opcode in opcodes {
+        let group: Vec<&Variant> = variants.iter().filter(|v| v.opcode == opcode).collect();
+        let _ = write!(out, "            {:#06b} => ", opcode);
+        match group.as_slice() {
+            [variant] => {
+                out.push_str(&render_construction(variant));
+                out.push_str(",\n");
+            }
+            [a, b] => {
+                let (bit, value) = a.mode.expect("variants sharing an opcode need a mode bit");
+                let (a, b) = if value == 1 { (a, b) } else { (b, a) };
+                let _ = writeln!(out, "{{");
+                let _ = writeln!(out, "                if (value >> {bit}) & 1 == 1 {{");
+                let _ = writeln!(out, "                    {}", render_construction(a));
+                out.push_str("                } else {\n");
+                let _ = writeln!(out, "                    {}", render_construction(b));
+                out.push_str("                }\n");
+                out.push_str("            }\n");
+            }
+            _ => panic!("opcode {opcode:#06b} has more than two variants"),
+        }
+    }
+
+    out.push_str("            _ => unreachable!(),\n");
+    out.push_str("        }\n");
+    out.push_str("    }\n");
+    out.push_str("}\n");
+    out
+}
+
+fn render_construction(variant: &Variant) -> String {
+    if variant.fields.is_empty() {
+        return format!("Opcode::{}", variant.name);
+    }
+    let mut out = format!("Opcode::{} {{ ", variant.name);
+    for field in &variant.fields {
+        let _ = write!(out, "{}: {}, ", field.name, field.decode_expr());
+    }
+    out.push('}');
+    out
+}
+
+fn render_encode(variants: &[Variant]) -> String {
+    let mut out = String::new();
+    out.push_str("impl Opcode {\n");
+    out.push_str("    /// Re-assembles the 16-bit instruction word this opcode decoded from.\n");
+    out.push_str("    ///\n");
+    out.push_str("    /// Only the tests call this for now; kept for the assembler this table is also meant to drive.\n");
+    out.push_str("    #[allow(dead_code)]\n");
+    out.push_str("    pub(crate) fn encode(&self) -> u16 {\n");
+    out.push_str("        match self {\n");
+    for variant in variants {
+        if variant.fields.is_empty() {
+            let _ = writeln!(
+                out,
+                "            Opcode::{} => {:#06b} << 12,",
+                variant.name, variant.opcode
+            );
+            continue;
+        }
+        let field_names: Vec<&str> = variant.fields.iter().map(|f| f.name.as_str()).collect();
+        let _ = writeln!(
+            out,
+            "            Opcode::{} {{ {} }} => {{",
+            variant.name,
+            field_names.join(", ")
+        );
+        let _ = writeln!(out, "                let opcode = {:#06b}u16 << 12;", variant.opcode);
+        let mode_bit = match variant.mode {
+            Some((_, 0)) | None => String::new(),
+            Some((bit, value)) => format!(" | ({value} << {bit})"),
+        };
+        let _ = write!(out, "                opcode{mode_bit}");
+        for field in &variant.fields {
+            let _ = write!(out, " | {}", field.encode_expr());
+        }
+        out.push('\n');
+        out.push_str("            }\n");
+    }
+    out.push_str("        }\n");
+    out.push_str("    }\n");
+    out.push_str("}\n");
+    out
+}
+
+/// Pipes `source` through `rustfmt` so the generated file matches the style
+/// of the hand-written modules around it. Falls back to the unformatted
+/// source if `rustfmt` isn't on `PATH`, so the build doesn't hard-depend on it.
+fn format_with_rustfmt(source: &str) -> String {
+    let mut child = match Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return source.to_string(),
+    };
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .expect("failed to pipe generated source into rustfmt");
+
+    let output = child.wait_with_output().expect("failed to run rustfmt");
+    if output.status.success() {
+        String::from_utf8(output.stdout).expect("rustfmt produced non-UTF-8 output")
+    } else {
+        source.to_string()
+    }
+}
+
+fn main() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let table_path = Path::new(&manifest_dir).join("instructions.in");
+    println!("cargo:rerun-if-changed={}", table_path.display());
+
+    let source = fs::read_to_string(&table_path).expect("failed to read instructions.in");
+    let variants = parse_table(&source);
+
+    let mut generated = String::new();
+    generated.push_str("// @generated by build.rs from instructions.in. Do not edit by hand.\n\n");
+    generated.push_str("use crate::vm::RegisterIndex;\n\n");
+    generated.push_str(
+        "/// Sign-extends the low `n` bits of `value` to a full 16-bit two's complement value.\n",
+    );
+    generated.push_str("fn sign_extend(value: u16, n: u32) -> u16 {\n");
+    generated.push_str("    if (value >> (n - 1)) & 1 == 1 {\n");
+    generated.push_str("        value | !((1u16 << n) - 1)\n");
+    generated.push_str("    } else {\n");
+    generated.push_str("        value\n");
+    generated.push_str("    }\n");
+    generated.push_str("}\n\n");
+    generated.push_str(&render_enum(&variants));
+    generated.push('\n');
+    generated.push_str(&render_decode(&variants));
+    generated.push('\n');
+    generated.push_str(&render_encode(&variants));
+
+    let out_path = Path::new(&manifest_dir).join("src/instrs.rs");
+    fs::write(out_path, format_with_rustfmt(&generated)).expect("failed to write src/instrs.rs");
+}
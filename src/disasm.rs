@@ -0,0 +1,99 @@
+//! Renders decoded `Opcode`s back into canonical LC-3 assembly syntax, the
+//! inverse of `instrs::Opcode::from`. Used by `Machine::disassemble_range` to
+//! inspect loaded images without running them.
+
+use crate::instrs::Opcode;
+
+fn reg(index: crate::vm::RegisterIndex) -> String {
+    format!("R{}", index.0)
+}
+
+fn branch_mnemonic(nzp: u16) -> String {
+    let mut mnemonic = String::from("BR");
+    if nzp & 0b100 != 0 {
+        mnemonic.push('n');
+    }
+    if nzp & 0b010 != 0 {
+        mnemonic.push('z');
+    }
+    if nzp & 0b001 != 0 {
+        mnemonic.push('p');
+    }
+    mnemonic
+}
+
+impl Opcode {
+    /// Renders this opcode in canonical LC-3 syntax, resolving PC-relative
+    /// offsets to the absolute address `pc + 1 + sext(offset)` they target.
+    pub(crate) fn disassemble(&self, pc: u16) -> String {
+        let target = |offset: u16| pc.wrapping_add(1).wrapping_add(offset);
+        match *self {
+            Opcode::Branch { nzp, pc_offset9 } => {
+                format!("{} x{:04X}", branch_mnemonic(nzp), target(pc_offset9))
+            }
+            Opcode::Add { dr, sr1, sr2 } => {
+                format!("ADD {}, {}, {}", reg(dr), reg(sr1), reg(sr2))
+            }
+            Opcode::AddImmediate { dr, sr1, imm5 } => {
+                format!("ADD {}, {}, #{}", reg(dr), reg(sr1), imm5 as i16)
+            }
+            Opcode::Load { dr, pc_offset9 } => {
+                format!("LD {}, x{:04X}", reg(dr), target(pc_offset9))
+            }
+            Opcode::Store { sr, pc_offset9 } => {
+                format!("ST {}, x{:04X}", reg(sr), target(pc_offset9))
+            }
+            Opcode::JumpRegister {
+                use_pc_offset,
+                base_r,
+                pc_offset11,
+            } => {
+                if use_pc_offset {
+                    format!("JSR x{:04X}", target(pc_offset11))
+                } else {
+                    format!("JSRR {}", reg(base_r))
+                }
+            }
+            Opcode::And { dr, sr1, sr2 } => {
+                format!("AND {}, {}, {}", reg(dr), reg(sr1), reg(sr2))
+            }
+            Opcode::AndImmediate { dr, sr1, imm5 } => {
+                format!("AND {}, {}, #{}", reg(dr), reg(sr1), imm5 as i16)
+            }
+            Opcode::LoadRegister {
+                dr,
+                base_r,
+                offset6,
+            } => {
+                format!("LDR {}, {}, #{}", reg(dr), reg(base_r), offset6 as i16)
+            }
+            Opcode::StoreRegister {
+                sr,
+                base_r,
+                offset6,
+            } => {
+                format!("STR {}, {}, #{}", reg(sr), reg(base_r), offset6 as i16)
+            }
+            Opcode::Rti => "RTI".to_string(),
+            Opcode::Not { dr, sr } => format!("NOT {}, {}", reg(dr), reg(sr)),
+            Opcode::LoadIndirect { dr, pc_offset9 } => {
+                format!("LDI {}, x{:04X}", reg(dr), target(pc_offset9))
+            }
+            Opcode::StoreIndirect { sr, pc_offset9 } => {
+                format!("STI {}, x{:04X}", reg(sr), target(pc_offset9))
+            }
+            Opcode::Jump { base_r } => {
+                if base_r == crate::vm::REG_R7 {
+                    "RET".to_string()
+                } else {
+                    format!("JMP {}", reg(base_r))
+                }
+            }
+            Opcode::Reserved => ".RESERVED".to_string(),
+            Opcode::LoadEffectiveAddress { dr, pc_offset9 } => {
+                format!("LEA {}, x{:04X}", reg(dr), target(pc_offset9))
+            }
+            Opcode::Trap { trapvect8 } => format!("TRAP x{:02X}", trapvect8),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod disasm;
+mod instrs;
+mod vm;
+
+pub use vm::{BufferConsole, Console, Fault, ImageLoadError, Machine, StdConsole, State};
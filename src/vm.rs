@@ -1,5 +1,9 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
 use std::ops::{Index, IndexMut};
 
+use crate::instrs::Opcode;
+
 const MEMORY_SIZE: usize = 1 << 16;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -35,8 +39,11 @@ impl IndexMut<u16> for Memory {
     }
 }
 
+/// Indexes a GPR or one of the extra slots `RegisterCluster` reserves for
+/// `REG_PC`/`REG_COND`. Also used by `instrs` to type register-index fields
+/// decoded out of an instruction word.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct RegisterIndex(u8);
+pub(crate) struct RegisterIndex(pub(crate) u8);
 
 impl Default for RegisterIndex {
     fn default() -> Self {
@@ -45,9 +52,48 @@ impl Default for RegisterIndex {
 }
 
 const REGISTER_COUNT: usize = 10;
+const REG_R0: RegisterIndex = RegisterIndex(0);
+const REG_R6: RegisterIndex = RegisterIndex(6);
+pub(crate) const REG_R7: RegisterIndex = RegisterIndex(7);
 const REG_PC: RegisterIndex = RegisterIndex(8);
 const REG_COND: RegisterIndex = RegisterIndex(9);
 
+const TRAP_GETC: u16 = 0x20;
+const TRAP_OUT: u16 = 0x21;
+const TRAP_PUTS: u16 = 0x22;
+const TRAP_IN: u16 = 0x23;
+const TRAP_PUTSP: u16 = 0x24;
+const TRAP_HALT: u16 = 0x25;
+
+const MMIO_KBSR: u16 = 0xfe00;
+const MMIO_KBDR: u16 = 0xfe02;
+const MMIO_DSR: u16 = 0xfe04;
+const MMIO_DDR: u16 = 0xfe06;
+const MMIO_READY: u16 = 0x8000;
+const MMIO_KBSR_INT_ENABLE: u16 = 0x4000;
+
+const COND_POSITIVE: u16 = 0b001;
+const COND_ZERO: u16 = 0b010;
+const COND_NEGATIVE: u16 = 0b100;
+
+const PSR_USER_MODE: u16 = 0x8000;
+const PSR_PRIORITY_SHIFT: u16 = 8;
+const PSR_PRIORITY_MASK: u16 = 0b111 << PSR_PRIORITY_SHIFT;
+const PSR_COND_MASK: u16 = 0b111;
+
+const USER_STACK_INIT: u16 = 0xfdff;
+const SUPERVISOR_STACK_INIT: u16 = 0x2fff;
+
+const KBD_INTERRUPT_VECTOR: u16 = 0x80;
+const KBD_INTERRUPT_PRIORITY: u16 = 4;
+const INTERRUPT_VECTOR_TABLE: u16 = 0x0100;
+
+const PRIVILEGE_VIOLATION_VECTOR: u16 = 0x00;
+const RESERVED_OPCODE_VECTOR: u16 = 0x01;
+/// Priority LC-3 exceptions conventionally run at, regardless of the
+/// interrupted program's own priority.
+const EXCEPTION_PRIORITY: u16 = 6;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct RegisterCluster {
     registers: [u16; REGISTER_COUNT],
@@ -75,148 +121,575 @@ impl IndexMut<RegisterIndex> for RegisterCluster {
     }
 }
 
-trait BitTool {
-    fn use_immediate_mode(self) -> bool;
-    fn read_dr(self) -> RegisterIndex;
-    fn read_sr1(self) -> RegisterIndex;
-    fn read_sr2(self) -> RegisterIndex;
-    fn read_imme5(self) -> u16;
-    fn read_pc_offset9(self) -> u16;
+/// The console a `Machine` performs trap-routine I/O against. Swapping the
+/// implementation lets the same decode/execute core run as an interactive
+/// interpreter or drive itself from a canned buffer in a test harness.
+pub trait Console: std::fmt::Debug {
+    fn read(&mut self) -> u8;
+    fn write(&mut self, byte: u8);
+
+    /// Whether a byte is available to `read` without blocking. Backs the
+    /// keyboard status register, so a `Machine` can poll for input instead
+    /// of only reading it via a trap.
+    fn key_ready(&mut self) -> bool;
 }
 
-impl BitTool for u16 {
-    fn use_immediate_mode(self) -> bool {
-        0b0000_0000_0001_0000 & self == 0b0000_0000_0001_0000
-    }
+/// Reads and writes one byte at a time against the process's stdin/stdout.
+#[derive(Debug, Default)]
+pub struct StdConsole;
 
-    fn read_dr(self) -> RegisterIndex {
-        let register_value = (self >> 9) & 0x07;
-        RegisterIndex(register_value as u8)
+impl Console for StdConsole {
+    fn read(&mut self) -> u8 {
+        let mut byte = [0u8; 1];
+        io::stdin().read_exact(&mut byte).unwrap_or_default();
+        byte[0]
     }
 
-    fn read_sr1(self) -> RegisterIndex {
-        let register_value = (self >> 5) & 0x07;
-        RegisterIndex(register_value as u8)
+    fn write(&mut self, byte: u8) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(&[byte]);
+        let _ = stdout.flush();
     }
 
-    fn read_sr2(self) -> RegisterIndex {
-        let register_value = self & 0x07;
-        RegisterIndex(register_value as u8)
+    /// Always reports a byte as ready: stdin is not polled, so a following
+    /// `read` blocks until a byte arrives.
+    fn key_ready(&mut self) -> bool {
+        true
     }
+}
 
-    fn read_imme5(self) -> u16 {
-        self & 0x1f
+/// A `Console` backed by in-memory queues instead of the real terminal, for
+/// driving a `Machine` from a test harness without touching stdin/stdout.
+#[derive(Debug, Default)]
+pub struct BufferConsole {
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+}
+
+impl BufferConsole {
+    pub fn with_input(bytes: impl IntoIterator<Item = u8>) -> Self {
+        Self {
+            input: bytes.into_iter().collect(),
+            output: Vec::new(),
+        }
     }
 
-    fn read_pc_offset9(self) -> u16 {
-        self & 0x7f
+    pub fn output(&self) -> &[u8] {
+        &self.output
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Opcode {
-    Branch,
-    Add {
-        dr: RegisterIndex,
-        sr1: RegisterIndex,
-        sr2: RegisterIndex,
-    },
-    AddImmediate {
-        dr: RegisterIndex,
-        sr1: RegisterIndex,
-        imm5: u16,
-    },
-    Load {
-        dr: RegisterIndex,
-        pc_offfset9: u16,
-    },
-    Store {
-        sr: RegisterIndex,
-        pc_offset9: u16,
-    },
-    JumpRegister,
-    And,
-    LoadRegister,
-    StoreRegister,
-    Unused,
-    Not,
-    LoadIndirect,
-    StoreIndirect,
-    Jump,
-    Reserved,
-    LoadEffectiveAddress,
-    Trap,
-}
-
-impl From<u16> for Opcode {
-    fn from(value: u16) -> Self {
-        let op = value >> 12;
-        match op {
-            0b0001 => {
-                if value.use_immediate_mode() {
-                    Opcode::AddImmediate {
-                        dr: value.read_dr(),
-                        sr1: value.read_sr1(),
-                        imm5: value.read_imme5(),
-                    }
-                } else {
-                    Opcode::Add {
-                        dr: value.read_dr(),
-                        sr1: value.read_sr1(),
-                        sr2: value.read_sr2(),
-                    }
-                }
-            }
-            0b0010 => Opcode::Load {
-                dr: value.read_dr(),
-                pc_offfset9: value.read_pc_offset9(),
-            },
-            0b0011 => Opcode::Store {
-                sr: value.read_dr(),
-                pc_offset9: value.read_pc_offset9(),
-            },
-            _ => unreachable!(),
-        }
+impl Console for BufferConsole {
+    fn read(&mut self) -> u8 {
+        self.input.pop_front().unwrap_or(0)
     }
+
+    fn write(&mut self, byte: u8) {
+        self.output.push(byte);
+    }
+
+    fn key_ready(&mut self) -> bool {
+        !self.input.is_empty()
+    }
+}
+
+/// A recoverable condition that stops `Machine::step`, in place of a panic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// `step` was called before `reset` primed the machine to run.
+    NotReset,
+    /// The program executed a `HALT` trap.
+    Halted,
+}
+
+/// Why `Machine::load_image` rejected an `.obj` image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageLoadError {
+    /// The byte slice does not divide evenly into 16-bit words.
+    OddByteCount,
+    /// The origin plus the program's length would run past `MEMORY_SIZE`.
+    OutOfRange,
+}
+
+/// The lifecycle of a `Machine`: freshly constructed, executing, or halted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// Constructed but never `reset`; `step` executes nothing and returns
+    /// `Fault::NotReset`.
+    Init,
+    /// Executing instructions.
+    Running,
+    /// Stopped by a `HALT` trap; `step` keeps returning `Fault::Halted`.
+    Halted,
 }
 
-#[derive(Debug, Clone)]
+const USER_START_ADDR: u16 = 0x3000;
+
+#[derive(Debug)]
 pub struct Machine {
     mem: Memory,
     registers: RegisterCluster,
+    console: Box<dyn Console>,
+    state: State,
+    /// Processor Status Register: privilege mode, priority level, and a
+    /// mirror of `REG_COND`'s N/Z/P bits.
+    psr: u16,
+    /// R6 while in user mode, saved here while a supervisor stack is active.
+    usp: u16,
+    /// R6 while in supervisor mode, saved here while a user stack is active.
+    ssp: u16,
+    /// Origin of the last image loaded via `load_image`, if any. `reset`
+    /// restarts execution here instead of `USER_START_ADDR` so it composes
+    /// with a non-`0x3000` origin regardless of call order.
+    entry_point: Option<u16>,
 }
 
 impl Machine {
     pub fn new() -> Self {
+        Self::with_console(Box::new(StdConsole))
+    }
+
+    pub fn with_console(console: Box<dyn Console>) -> Self {
         Self {
             mem: Memory::new(),
             registers: RegisterCluster::default(),
+            console,
+            state: State::Init,
+            psr: PSR_USER_MODE | COND_ZERO,
+            usp: USER_STACK_INIT,
+            ssp: SUPERVISOR_STACK_INIT,
+            entry_point: None,
         }
     }
 
+    /// Zeroes the registers, sets `REG_PC` to the last image's origin (or the
+    /// conventional user program start address if none was loaded), sets
+    /// `REG_COND` to Z and R6 to the initial user stack pointer, resets the
+    /// PSR to unprivileged/priority 0, and transitions to `Running`.
+    pub fn reset(&mut self) {
+        self.registers = RegisterCluster::default();
+        self.registers[REG_PC] = self.entry_point.unwrap_or(USER_START_ADDR);
+        self.registers[REG_COND] = COND_ZERO;
+        self.usp = USER_STACK_INIT;
+        self.ssp = SUPERVISOR_STACK_INIT;
+        self.registers[REG_R6] = self.usp;
+        self.psr = PSR_USER_MODE | COND_ZERO;
+        self.state = State::Running;
+    }
+
     pub fn load(&mut self, block: &[u16], offset: u16) {
         self.mem.load(block, offset);
     }
 
-    pub fn step(&mut self) {
-        let instr = self.mem[self.registers[REG_PC]];
-        self.registers[REG_PC] += 2;
+    /// Loads a standard LC-3 `.obj` image: a big-endian origin word followed
+    /// by the program's big-endian words, placed consecutively from there.
+    /// Leaves `REG_PC` pointing at the origin and records it so a later
+    /// `reset()` restarts there instead of `USER_START_ADDR`.
+    pub fn load_image(&mut self, bytes: &[u8]) -> Result<(), ImageLoadError> {
+        if !bytes.len().is_multiple_of(2) {
+            return Err(ImageLoadError::OddByteCount);
+        }
+
+        let mut words = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+
+        let origin = match words.next() {
+            Some(origin) => origin,
+            None => return Ok(()),
+        };
+
+        let program: Vec<u16> = words.collect();
+        if origin as usize + program.len() > MEMORY_SIZE {
+            return Err(ImageLoadError::OutOfRange);
+        }
+
+        self.mem.load(&program, origin);
+        self.registers[REG_PC] = origin;
+        self.entry_point = Some(origin);
+        Ok(())
+    }
+
+    fn set_cond(&mut self, value: u16) {
+        let cond = if value & 0x8000 != 0 {
+            COND_NEGATIVE
+        } else if value == 0 {
+            COND_ZERO
+        } else {
+            COND_POSITIVE
+        };
+        self.registers[REG_COND] = cond;
+        self.psr = (self.psr & !PSR_COND_MASK) | cond;
+    }
+
+    /// Reads a memory cell, routing the device-register range through the
+    /// memory-mapped I/O table instead of plain RAM.
+    fn mem_read(&mut self, addr: u16) -> u16 {
+        match addr {
+            MMIO_KBSR => {
+                self.mem[addr] = (self.mem[addr] & !MMIO_READY)
+                    | if self.console.key_ready() {
+                        MMIO_READY
+                    } else {
+                        0
+                    };
+                self.mem[addr]
+            }
+            MMIO_KBDR => {
+                if self.mem[MMIO_KBSR] & MMIO_READY != 0 {
+                    self.mem[addr] = self.console.read() as u16;
+                    self.mem[MMIO_KBSR] &= !MMIO_READY;
+                }
+                self.mem[addr]
+            }
+            MMIO_DSR => {
+                self.mem[addr] = MMIO_READY;
+                self.mem[addr]
+            }
+            _ => self.mem[addr],
+        }
+    }
+
+    /// Writes a memory cell, routing the device-register range through the
+    /// memory-mapped I/O table instead of plain RAM.
+    fn mem_write(&mut self, addr: u16, val: u16) {
+        self.mem[addr] = val;
+        if addr == MMIO_DDR {
+            self.console.write(val as u8);
+        }
+    }
+
+    /// Pushes the current PSR and PC onto the supervisor stack (swapping in
+    /// the supervisor stack pointer if coming from user mode), raises the
+    /// priority to `priority`, and loads PC from the vector table at
+    /// `table_base + vector`.
+    fn enter_interrupt(&mut self, table_base: u16, vector: u16, priority: u16) {
+        if self.psr & PSR_USER_MODE != 0 {
+            self.usp = self.registers[REG_R6];
+            self.registers[REG_R6] = self.ssp;
+        }
+
+        let mut sp = self.registers[REG_R6];
+        sp = sp.wrapping_sub(1);
+        self.mem_write(sp, self.registers[REG_PC]);
+        sp = sp.wrapping_sub(1);
+        self.mem_write(sp, self.psr);
+        self.registers[REG_R6] = sp;
+
+        self.psr = (self.psr & !PSR_USER_MODE & !PSR_PRIORITY_MASK)
+            | (priority << PSR_PRIORITY_SHIFT);
+        self.registers[REG_PC] = self.mem_read(table_base.wrapping_add(vector));
+    }
+
+    /// Executes a single instruction, never panicking, so a `Machine` is
+    /// safe to embed. A reserved opcode or an `RTI` outside supervisor mode
+    /// is routed through the exception vector table like a device
+    /// interrupt rather than surfacing as an `Err`. Returns `Fault::NotReset`
+    /// instead of stepping if called before `reset` has primed the machine
+    /// to run.
+    pub fn step(&mut self) -> Result<(), Fault> {
+        match self.state {
+            State::Init => return Err(Fault::NotReset),
+            State::Halted => return Err(Fault::Halted),
+            State::Running => {}
+        }
+
+        let keyboard_ie = self.mem[MMIO_KBSR] & MMIO_KBSR_INT_ENABLE != 0;
+        let current_priority = (self.psr & PSR_PRIORITY_MASK) >> PSR_PRIORITY_SHIFT;
+        if keyboard_ie && self.console.key_ready() && KBD_INTERRUPT_PRIORITY > current_priority {
+            self.enter_interrupt(INTERRUPT_VECTOR_TABLE, KBD_INTERRUPT_VECTOR, KBD_INTERRUPT_PRIORITY);
+            return Ok(());
+        }
+
+        let instr = self.mem_read(self.registers[REG_PC]);
+        self.registers[REG_PC] = self.registers[REG_PC].wrapping_add(1);
         let opcode = Opcode::from(instr);
 
         match opcode {
+            Opcode::Branch { nzp, pc_offset9 } => {
+                if nzp & self.registers[REG_COND] != 0 {
+                    self.registers[REG_PC] = self.registers[REG_PC].wrapping_add(pc_offset9);
+                }
+            }
             Opcode::Add { dr, sr1, sr2 } => {
-                self.registers[dr] = self.registers[sr1] + self.registers[sr2];
+                self.registers[dr] = self.registers[sr1].wrapping_add(self.registers[sr2]);
+                self.set_cond(self.registers[dr]);
             }
             Opcode::AddImmediate { dr, sr1, imm5 } => {
-                self.registers[dr] = self.registers[sr1] + imm5;
+                self.registers[dr] = self.registers[sr1].wrapping_add(imm5);
+                self.set_cond(self.registers[dr]);
             }
-            Opcode::Load { dr, pc_offfset9 } => {
-                self.registers[dr] = self.mem[self.registers[REG_PC] + pc_offfset9];
+            Opcode::Load { dr, pc_offset9 } => {
+                let addr = self.registers[REG_PC].wrapping_add(pc_offset9);
+                self.registers[dr] = self.mem_read(addr);
+                self.set_cond(self.registers[dr]);
             }
             Opcode::Store { sr, pc_offset9 } => {
-                self.mem[self.registers[REG_PC] + pc_offset9] = self.registers[sr];
+                let addr = self.registers[REG_PC].wrapping_add(pc_offset9);
+                self.mem_write(addr, self.registers[sr]);
+            }
+            Opcode::JumpRegister {
+                use_pc_offset,
+                base_r,
+                pc_offset11,
+            } => {
+                let return_addr = self.registers[REG_PC];
+                self.registers[REG_PC] = if use_pc_offset {
+                    return_addr.wrapping_add(pc_offset11)
+                } else {
+                    self.registers[base_r]
+                };
+                self.registers[REG_R7] = return_addr;
+            }
+            Opcode::And { dr, sr1, sr2 } => {
+                self.registers[dr] = self.registers[sr1] & self.registers[sr2];
+                self.set_cond(self.registers[dr]);
+            }
+            Opcode::AndImmediate { dr, sr1, imm5 } => {
+                self.registers[dr] = self.registers[sr1] & imm5;
+                self.set_cond(self.registers[dr]);
+            }
+            Opcode::LoadRegister {
+                dr,
+                base_r,
+                offset6,
+            } => {
+                let addr = self.registers[base_r].wrapping_add(offset6);
+                self.registers[dr] = self.mem_read(addr);
+                self.set_cond(self.registers[dr]);
+            }
+            Opcode::StoreRegister {
+                sr,
+                base_r,
+                offset6,
+            } => {
+                let addr = self.registers[base_r].wrapping_add(offset6);
+                self.mem_write(addr, self.registers[sr]);
+            }
+            Opcode::Rti => {
+                if self.psr & PSR_USER_MODE != 0 {
+                    self.enter_interrupt(
+                        INTERRUPT_VECTOR_TABLE,
+                        PRIVILEGE_VIOLATION_VECTOR,
+                        EXCEPTION_PRIORITY,
+                    );
+                } else {
+                    let mut sp = self.registers[REG_R6];
+                    let popped_psr = self.mem_read(sp);
+                    sp = sp.wrapping_add(1);
+                    let popped_pc = self.mem_read(sp);
+                    sp = sp.wrapping_add(1);
+                    self.registers[REG_R6] = sp;
+
+                    self.registers[REG_PC] = popped_pc;
+                    self.psr = popped_psr;
+                    self.registers[REG_COND] = popped_psr & PSR_COND_MASK;
+                    if popped_psr & PSR_USER_MODE != 0 {
+                        self.ssp = self.registers[REG_R6];
+                        self.registers[REG_R6] = self.usp;
+                    }
+                }
+            }
+            Opcode::Not { dr, sr } => {
+                self.registers[dr] = !self.registers[sr];
+                self.set_cond(self.registers[dr]);
+            }
+            Opcode::LoadIndirect { dr, pc_offset9 } => {
+                let indirect_addr = self.registers[REG_PC].wrapping_add(pc_offset9);
+                let addr = self.mem_read(indirect_addr);
+                self.registers[dr] = self.mem_read(addr);
+                self.set_cond(self.registers[dr]);
+            }
+            Opcode::StoreIndirect { sr, pc_offset9 } => {
+                let indirect_addr = self.registers[REG_PC].wrapping_add(pc_offset9);
+                let addr = self.mem_read(indirect_addr);
+                self.mem_write(addr, self.registers[sr]);
+            }
+            Opcode::Jump { base_r } => {
+                self.registers[REG_PC] = self.registers[base_r];
+            }
+            Opcode::Reserved => {
+                self.enter_interrupt(INTERRUPT_VECTOR_TABLE, RESERVED_OPCODE_VECTOR, EXCEPTION_PRIORITY);
+            }
+            Opcode::LoadEffectiveAddress { dr, pc_offset9 } => {
+                self.registers[dr] = self.registers[REG_PC].wrapping_add(pc_offset9);
+                self.set_cond(self.registers[dr]);
+            }
+            Opcode::Trap { trapvect8 } => {
+                self.registers[REG_R7] = self.registers[REG_PC];
+                self.execute_trap(trapvect8)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Steps the machine until a fault (including a clean `Halted`) stops it.
+    pub fn run(&mut self) -> Fault {
+        loop {
+            if let Err(fault) = self.step() {
+                return fault;
             }
-            _ => unreachable!(),
         }
     }
+
+    /// Runs to completion, i.e. until the program halts.
+    pub fn run_until_halt(&mut self) {
+        while self.state == State::Running {
+            let _ = self.step();
+        }
+    }
+
+    /// Runs at most `n` instructions, stopping early if the program halts.
+    pub fn run_n(&mut self, n: usize) {
+        for _ in 0..n {
+            if self.state != State::Running {
+                break;
+            }
+            let _ = self.step();
+        }
+    }
+
+    /// Disassembles `len` words of memory starting at `start`, returning each
+    /// instruction's address paired with its rendered LC-3 syntax.
+    pub fn disassemble_range(&self, start: u16, len: u16) -> Vec<(u16, String)> {
+        (0..len)
+            .map(|i| {
+                let addr = start.wrapping_add(i);
+                let opcode = Opcode::from(self.mem[addr]);
+                (addr, opcode.disassemble(addr))
+            })
+            .collect()
+    }
+
+    fn execute_trap(&mut self, trapvect8: u16) -> Result<(), Fault> {
+        match trapvect8 {
+            TRAP_GETC => {
+                let byte = self.console.read();
+                self.registers[REG_R0] = byte as u16;
+            }
+            TRAP_OUT => {
+                let byte = self.registers[REG_R0] as u8;
+                self.console.write(byte);
+            }
+            TRAP_PUTS => {
+                let mut addr = self.registers[REG_R0];
+                loop {
+                    let word = self.mem_read(addr);
+                    if word == 0 {
+                        break;
+                    }
+                    self.console.write(word as u8);
+                    addr = addr.wrapping_add(1);
+                }
+            }
+            TRAP_IN => {
+                for byte in b"Input a character> " {
+                    self.console.write(*byte);
+                }
+                let byte = self.console.read();
+                self.console.write(byte);
+                self.registers[REG_R0] = byte as u16;
+            }
+            TRAP_PUTSP => {
+                let mut addr = self.registers[REG_R0];
+                loop {
+                    let word = self.mem_read(addr);
+                    let low = word as u8;
+                    if low == 0 {
+                        break;
+                    }
+                    self.console.write(low);
+                    let high = (word >> 8) as u8;
+                    if high == 0 {
+                        break;
+                    }
+                    self.console.write(high);
+                    addr = addr.wrapping_add(1);
+                }
+            }
+            TRAP_HALT => {
+                self.state = State::Halted;
+                return Err(Fault::Halted);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine_with_input(bytes: impl IntoIterator<Item = u8>) -> Machine {
+        Machine::with_console(Box::new(BufferConsole::with_input(bytes)))
+    }
+
+    #[test]
+    fn getc_trap_reads_from_buffer_console() {
+        let mut machine = machine_with_input([0x41]);
+        machine.load(
+            &[Opcode::Trap { trapvect8: TRAP_GETC }.encode(), Opcode::Trap { trapvect8: TRAP_HALT }.encode()],
+            USER_START_ADDR,
+        );
+        machine.reset();
+
+        machine.run_until_halt();
+
+        assert_eq!(machine.registers[REG_R0], 0x41);
+        assert_eq!(machine.state, State::Halted);
+    }
+
+    #[test]
+    fn reserved_opcode_enters_the_exception_vector_table_instead_of_panicking() {
+        let mut machine = machine_with_input([]);
+        machine.load(&[Opcode::Reserved.encode()], USER_START_ADDR);
+        machine.load(&[0x5000], INTERRUPT_VECTOR_TABLE.wrapping_add(RESERVED_OPCODE_VECTOR));
+        machine.reset();
+
+        machine.step().unwrap();
+
+        assert_eq!(machine.registers[REG_PC], 0x5000);
+    }
+
+    #[test]
+    fn run_without_reset_reports_not_reset_instead_of_spinning() {
+        let mut machine = machine_with_input([]);
+        machine.load(&[Opcode::Trap { trapvect8: TRAP_HALT }.encode()], USER_START_ADDR);
+
+        assert_eq!(machine.run(), Fault::NotReset);
+    }
+
+    #[test]
+    fn reset_restarts_at_the_loaded_images_origin() {
+        let mut machine = machine_with_input([]);
+        let mut image = 0x4000u16.to_be_bytes().to_vec();
+        image.extend(Opcode::Trap { trapvect8: TRAP_HALT }.encode().to_be_bytes());
+        machine.load_image(&image).unwrap();
+
+        machine.reset();
+
+        assert_eq!(machine.registers[REG_PC], 0x4000);
+    }
+
+    #[test]
+    fn gated_keyboard_interrupt_jumps_to_the_vector_table_entry() {
+        let mut machine = machine_with_input([0x61]);
+        machine.load(&[MMIO_KBSR_INT_ENABLE], MMIO_KBSR);
+        machine.load(&[0x5000], INTERRUPT_VECTOR_TABLE.wrapping_add(KBD_INTERRUPT_VECTOR));
+        machine.load(&[0xbeef], 0x0200);
+        machine.reset();
+
+        machine.step().unwrap();
+
+        assert_eq!(machine.registers[REG_PC], 0x5000);
+    }
 }